@@ -0,0 +1,66 @@
+//! Client code generation for SpacetimeDB modules.
+//!
+//! A [`Lang`] backend turns a [`ModuleDef`] into a set of `(filename, contents)`
+//! pairs that make up a generated client package. Each language backend decides
+//! how tables, reducers and standalone types map onto its own idioms; the
+//! [`generate`] driver walks the module and dispatches to the backend one
+//! definition at a time.
+
+use spacetimedb_schema::def::{ModuleDef, ReducerDef, TableDef, TypeDef};
+
+mod python;
+
+pub use python::Python;
+
+/// A target language backend.
+///
+/// The driver calls one `generate_*` method per definition and one
+/// [`Lang::generate_globals`] pass for package-level files (barrels, prelude
+/// modules, and so on). Filenames are produced separately so the driver can
+/// keep a stable emission order without asking the backend to re-render.
+pub trait Lang {
+    /// File the given table's generated class is written to.
+    fn table_filename(&self, module: &ModuleDef, table: &TableDef) -> String;
+
+    /// File the given standalone type's generated class is written to.
+    fn type_filename(&self, module: &ModuleDef, typ: &TypeDef) -> String;
+
+    /// File the given reducer's generated bindings are written to.
+    fn reducer_filename(&self, module: &ModuleDef, reducer: &ReducerDef) -> String;
+
+    /// Render the client-cache row class for `table`.
+    fn generate_table(&self, module: &ModuleDef, table: &TableDef) -> String;
+
+    /// Render the class for a standalone (non-table) type.
+    fn generate_type(&self, module: &ModuleDef, typ: &TypeDef) -> String;
+
+    /// Render the argument class and call stub for `reducer`.
+    fn generate_reducer(&self, module: &ModuleDef, reducer: &ReducerDef) -> String;
+
+    /// Render package-level files that aggregate the per-definition output.
+    fn generate_globals(&self, module: &ModuleDef) -> Vec<(String, String)>;
+}
+
+/// Generate a client package for `module` using the `lang` backend.
+///
+/// Returns the generated files as `(filename, contents)` pairs in a stable
+/// order: standalone types, tables, reducers, then package globals.
+pub fn generate(module: &ModuleDef, lang: &impl Lang) -> Vec<(String, String)> {
+    let mut files = Vec::new();
+
+    for typ in module.types() {
+        files.push((lang.type_filename(module, typ), lang.generate_type(module, typ)));
+    }
+    for table in module.tables() {
+        files.push((lang.table_filename(module, table), lang.generate_table(module, table)));
+    }
+    for reducer in module.reducers() {
+        files.push((
+            lang.reducer_filename(module, reducer),
+            lang.generate_reducer(module, reducer),
+        ));
+    }
+
+    files.extend(lang.generate_globals(module));
+    files
+}
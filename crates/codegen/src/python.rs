@@ -0,0 +1,933 @@
+//! Python client backend.
+//!
+//! Emits one module per table/type/reducer plus a package barrel. Generated
+//! classes are plain dataclasses that mirror the module's `ProductType`s; the
+//! serialization helpers walk the field `AlgebraicType`s so the layout always
+//! tracks the schema.
+
+use spacetimedb_lib::sats::{AlgebraicType, ProductType, ProductTypeElement};
+use spacetimedb_schema::def::{ModuleDef, ReducerDef, TableDef, TypeDef};
+
+use crate::Lang;
+
+/// Python client code generator.
+pub struct Python;
+
+impl Lang for Python {
+    fn table_filename(&self, _module: &ModuleDef, table: &TableDef) -> String {
+        format!("{}.py", snake_case(table.name.as_ref()))
+    }
+
+    fn type_filename(&self, _module: &ModuleDef, typ: &TypeDef) -> String {
+        format!("{}.py", snake_case(type_name(typ)))
+    }
+
+    fn reducer_filename(&self, _module: &ModuleDef, reducer: &ReducerDef) -> String {
+        format!("{}_reducer.py", snake_case(reducer.name.as_ref()))
+    }
+
+    fn generate_table(&self, module: &ModuleDef, table: &TableDef) -> String {
+        let product = resolve_product(module, table.product_type_ref);
+        let mut out = render_dataclass(module, &pascal_case(table.name.as_ref()), product);
+        out.push_str(&render_accessors(table, product));
+        out
+    }
+
+    fn generate_type(&self, module: &ModuleDef, typ: &TypeDef) -> String {
+        let class = pascal_case(type_name(typ));
+        match resolve_type(module, typ.ty) {
+            AlgebraicType::Product(product) => render_dataclass(module, &class, product),
+            // A standalone option type is a `typing.Optional` alias over the inner type.
+            AlgebraicType::Sum(sum) if as_option(sum).is_some() => {
+                let inner = as_option(sum).unwrap();
+                format!("import typing\n\n{} = typing.Optional[{}]\n", class, python_type(module, inner))
+            }
+            AlgebraicType::Sum(sum) => render_union(module, &class, sum),
+            // A type alias over a primitive/array resolves to a plain assignment.
+            other => format!("import typing\n\n{} = {}\n", class, python_type(module, other)),
+        }
+    }
+
+    fn generate_reducer(&self, module: &ModuleDef, reducer: &ReducerDef) -> String {
+        let class = format!("{}Args", pascal_case(reducer.name.as_ref()));
+        let params = &reducer.params;
+
+        // The argument dataclass serializes its fields in declaration order so
+        // the call payload matches the reducer signature on the host.
+        let mut out = render_dataclass(module, &class, params);
+        out.push('\n');
+
+        // Thin call stub: build the argument class and hand its serialized
+        // payload to the client connection.
+        let call_params = params
+            .elements
+            .iter()
+            .map(|e| snake_case(field_name(e)))
+            .collect::<Vec<_>>();
+        let signature = std::iter::once("conn".to_string())
+            .chain(call_params.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let forwarded = call_params
+            .iter()
+            .map(|n| format!("{}={}", n, n))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        out.push_str(&format!("def {}({}):\n", snake_case(reducer.name.as_ref()), signature));
+        out.push_str(&format!(
+            "    args = {}({})\n",
+            class,
+            forwarded
+        ));
+        out.push_str(&format!(
+            "    return conn.call_reducer(\"{}\", args.serialize())\n",
+            reducer.name.as_ref()
+        ));
+        out
+    }
+
+    fn generate_globals(&self, module: &ModuleDef) -> Vec<(String, String)> {
+        // Aggregate every generated symbol into a single top-level import
+        // surface, so downstream code can write `from spacetime_types import
+        // User` instead of reaching into the per-file module path.
+        let mut out = String::new();
+        let mut exported: Vec<String> = Vec::new();
+
+        // Classes the client cache must be able to decode, and the reducer
+        // functions the connection wires up.
+        let mut row_classes: Vec<String> = Vec::new();
+        let mut reducer_funcs: Vec<String> = Vec::new();
+
+        for typ in module.types() {
+            let class = pascal_case(type_name(typ));
+            out.push_str(&format!("from .{} import {}\n", snake_case(type_name(typ)), class));
+            exported.push(class.clone());
+            row_classes.push(class);
+        }
+        for table in module.tables() {
+            let class = pascal_case(table.name.as_ref());
+            out.push_str(&format!("from .{} import {}\n", snake_case(table.name.as_ref()), class));
+            exported.push(class.clone());
+            row_classes.push(class);
+        }
+        for reducer in module.reducers() {
+            let func = snake_case(reducer.name.as_ref());
+            let args = format!("{}Args", pascal_case(reducer.name.as_ref()));
+            out.push_str(&format!(
+                "from .{}_reducer import {}, {}\n",
+                snake_case(reducer.name.as_ref()),
+                func,
+                args
+            ));
+            exported.push(func.clone());
+            exported.push(args);
+            reducer_funcs.push(func);
+        }
+
+        out.push_str("from .connection import connect\n");
+        exported.push("connect".to_string());
+
+        out.push('\n');
+        out.push_str(&format!(
+            "__all__ = [{}]\n",
+            exported
+                .iter()
+                .map(|name| format!("\"{}\"", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+
+        vec![
+            ("_bsatn.py".to_string(), BSATN_RUNTIME.to_string()),
+            ("connection.py".to_string(), render_connection(&row_classes, &reducer_funcs)),
+            ("__init__.py".to_string(), out),
+        ]
+    }
+}
+
+/// Render the package connection entry point. The helper rewrites `http(s)`
+/// host URIs to the `ws(s)` transport the SDK actually speaks, then registers
+/// every generated row class and reducer so the caller gets a ready-to-use
+/// connection in one call.
+fn render_connection(row_classes: &[String], reducer_funcs: &[String]) -> String {
+    let mut out = String::new();
+    out.push_str("from __future__ import annotations\n\n");
+
+    // Import the generated symbols through the barrel so registration does not
+    // need to know each per-file module path.
+    let symbols = row_classes
+        .iter()
+        .chain(reducer_funcs.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+    if !symbols.is_empty() {
+        out.push_str(&format!("from . import ({})\n\n", symbols.join(", ")));
+    }
+
+    out.push_str(
+        "def _normalize_uri(uri: str) -> str:\n    \"\"\"Rewrite an http(s) host URI to the ws(s) transport scheme.\"\"\"\n    if uri.startswith(\"https://\"):\n        return \"wss://\" + uri[len(\"https://\"):]\n    if uri.startswith(\"http://\"):\n        return \"ws://\" + uri[len(\"http://\"):]\n    return uri\n\n\n",
+    );
+
+    out.push_str("def connect(uri: str, *args, **kwargs):\n");
+    out.push_str("    from spacetimedb_sdk import SpacetimeClient\n\n");
+    out.push_str("    client = SpacetimeClient(_normalize_uri(uri), *args, **kwargs)\n");
+    out.push_str(&format!(
+        "    client.register_row_types([{}])\n",
+        row_classes.join(", ")
+    ));
+    for func in reducer_funcs {
+        out.push_str(&format!("    client.register_reducer({})\n", func));
+    }
+    out.push_str("    return client\n");
+    out
+}
+
+/// Render the client-cache query helpers a table exposes: a unique
+/// `find_by_<col>` for the primary key and for each unique index, and a
+/// `filter_by_<col>` iterator for each non-unique index. The column names are
+/// resolved from the table's `ProductType`, and every helper scans the rows
+/// held in the client cache so callers avoid manual row scans.
+fn render_accessors(table: &TableDef, product: &ProductType) -> String {
+    let class = pascal_case(table.name.as_ref());
+    let mut out = String::new();
+
+    let pk_col = table.primary_key.as_ref().and_then(|cols| cols.first()).map(|c| *c as usize);
+    if let Some(col) = pk_col {
+        out.push_str(&find_by(&class, &column_name(product, col)));
+    }
+
+    for index in table.indexes.iter() {
+        let Some(col) = index.algorithm.columns().iter().next() else {
+            continue;
+        };
+        let col = col.idx();
+        // Skip an index that merely duplicates the primary key's lookup.
+        if pk_col == Some(col) {
+            continue;
+        }
+        let name = column_name(product, col);
+        if index_is_unique(table, col) {
+            out.push_str(&find_by(&class, &name));
+        } else {
+            out.push_str(&filter_by(&class, &name));
+        }
+    }
+
+    out
+}
+
+/// A `find_by_<col>` classmethod returning the first matching row, or `None`.
+fn find_by(class: &str, col_name: &str) -> String {
+    format!(
+        "\n    @classmethod\n    def find_by_{name}(cls, conn, value) -> typing.Optional[{class}]:\n        for row in conn.cache(cls):\n            if row.{name} == value:\n                return row\n        return None\n",
+        name = col_name,
+        class = class,
+    )
+}
+
+/// A `filter_by_<col>` classmethod yielding every matching row.
+fn filter_by(class: &str, col_name: &str) -> String {
+    format!(
+        "\n    @classmethod\n    def filter_by_{name}(cls, conn, value) -> typing.Iterator[{class}]:\n        return (row for row in conn.cache(cls) if row.{name} == value)\n",
+        name = col_name,
+        class = class,
+    )
+}
+
+/// Whether a unique constraint covers exactly the single column `col`.
+fn index_is_unique(table: &TableDef, col: usize) -> bool {
+    table.constraints.iter().any(|c| constraint_unique_column(c) == Some(col))
+}
+
+/// The single column a unique constraint covers, or `None` for non-unique or
+/// multi-column constraints.
+fn constraint_unique_column(constraint: &spacetimedb_schema::def::ConstraintDef) -> Option<usize> {
+    match &constraint.data {
+        spacetimedb_schema::def::ConstraintData::Unique(unique) => {
+            let mut cols = unique.columns.iter();
+            let col = cols.next()?;
+            if cols.next().is_none() {
+                Some(col.idx())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn column_name(product: &ProductType, col: usize) -> String {
+    product
+        .elements
+        .get(col)
+        .map(|e| snake_case(field_name(e)))
+        .unwrap_or_else(|| format!("col_{}", col))
+}
+
+const PREAMBLE: &str = "from __future__ import annotations\nimport typing\nfrom dataclasses import dataclass\nfrom ._bsatn import BsatnWriter, BsatnReader\n\n";
+
+/// Shared BSATN (binary SATS) reader/writer runtime emitted once per package.
+/// Primitives are fixed-width little-endian, `bool` is a single byte, `String`
+/// is a `u32` length prefix plus UTF-8 bytes, and sums write a `u8` tag.
+const BSATN_RUNTIME: &str = r#"import struct
+
+
+class BsatnWriter:
+    def __init__(self):
+        self.buf = bytearray()
+
+    def finish(self) -> bytes:
+        return bytes(self.buf)
+
+    def write_bytes(self, b):
+        self.buf.extend(b)
+
+    def write_bool(self, v):
+        self.buf.append(1 if v else 0)
+
+    def write_u8(self, v):
+        self.buf.append(v & 0xFF)
+
+    def write_u16(self, v):
+        self.buf.extend(struct.pack("<H", v))
+
+    def write_u32(self, v):
+        self.buf.extend(struct.pack("<I", v))
+
+    def write_u64(self, v):
+        self.buf.extend(struct.pack("<Q", v))
+
+    def write_u128(self, v):
+        self.buf.extend(v.to_bytes(16, "little", signed=False))
+
+    def write_i8(self, v):
+        self.buf.extend(struct.pack("<b", v))
+
+    def write_i16(self, v):
+        self.buf.extend(struct.pack("<h", v))
+
+    def write_i32(self, v):
+        self.buf.extend(struct.pack("<i", v))
+
+    def write_i64(self, v):
+        self.buf.extend(struct.pack("<q", v))
+
+    def write_i128(self, v):
+        self.buf.extend(v.to_bytes(16, "little", signed=True))
+
+    def write_f32(self, v):
+        self.buf.extend(struct.pack("<f", v))
+
+    def write_f64(self, v):
+        self.buf.extend(struct.pack("<d", v))
+
+    def write_string(self, v):
+        data = v.encode("utf-8")
+        self.write_u32(len(data))
+        self.buf.extend(data)
+
+
+class BsatnReader:
+    def __init__(self, buf):
+        self.buf = buf
+        self.pos = 0
+
+    def _take(self, n):
+        chunk = self.buf[self.pos:self.pos + n]
+        self.pos += n
+        return chunk
+
+    def read_bool(self):
+        return self._take(1)[0] != 0
+
+    def read_u8(self):
+        return self._take(1)[0]
+
+    def read_u16(self):
+        return struct.unpack("<H", self._take(2))[0]
+
+    def read_u32(self):
+        return struct.unpack("<I", self._take(4))[0]
+
+    def read_u64(self):
+        return struct.unpack("<Q", self._take(8))[0]
+
+    def read_u128(self):
+        return int.from_bytes(self._take(16), "little", signed=False)
+
+    def read_i8(self):
+        return struct.unpack("<b", self._take(1))[0]
+
+    def read_i16(self):
+        return struct.unpack("<h", self._take(2))[0]
+
+    def read_i32(self):
+        return struct.unpack("<i", self._take(4))[0]
+
+    def read_i64(self):
+        return struct.unpack("<q", self._take(8))[0]
+
+    def read_i128(self):
+        return int.from_bytes(self._take(16), "little", signed=True)
+
+    def read_f32(self):
+        return struct.unpack("<f", self._take(4))[0]
+
+    def read_f64(self):
+        return struct.unpack("<d", self._take(8))[0]
+
+    def read_string(self):
+        n = self.read_u32()
+        return self._take(n).decode("utf-8")
+
+    def read_option(self, decode):
+        return decode() if self.read_u8() == 0 else None
+
+    def read_array(self, decode):
+        return [decode() for _ in range(self.read_u32())]
+"#;
+
+/// Render a `@dataclass` whose attributes mirror `product`'s elements in
+/// declaration order.
+fn render_dataclass(module: &ModuleDef, class: &str, product: &ProductType) -> String {
+    let mut out = String::new();
+    out.push_str(PREAMBLE);
+    // Pull in any sibling classes this product references so the lazy
+    // annotations and BSATN recursion resolve against real names.
+    for (stem, class_name) in referenced_types(module, product) {
+        out.push_str(&format!("from .{} import {}\n", stem, class_name));
+    }
+    out.push('\n');
+    // Fields stay in SATS declaration order (callers rely on it for the
+    // positional call payload and BSATN layout); `kw_only` lets option fields
+    // default to `None` without the trailing-default ordering constraint.
+    out.push_str("@dataclass(kw_only=True)\n");
+    out.push_str(&format!("class {}:\n", class));
+
+    for element in product.elements.iter() {
+        let default = if is_option(&element.algebraic_type) { " = None" } else { "" };
+        out.push_str(&format!(
+            "    {}: {}{}\n",
+            snake_case(field_name(element)),
+            python_type(module, &element.algebraic_type),
+            default
+        ));
+    }
+
+    // Map each Python attribute back to its original SATS field name so the
+    // serialization layer keeps talking the wire names even after the rename.
+    if !product.elements.is_empty() {
+        let mapping = product
+            .elements
+            .iter()
+            .map(|e| format!("\"{}\": \"{}\"", snake_case(field_name(e)), sats_name(field_name(e))))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!("    __sats_names__: typing.ClassVar[dict] = {{{}}}\n", mapping));
+    }
+
+    out.push('\n');
+    // Fields are emitted in declaration order; callers rely on this for the
+    // positional call payload. An empty product still gets a `serialize` so the
+    // reducer call stub can invoke it unconditionally.
+    let names = product
+        .elements
+        .iter()
+        .map(|e| snake_case(field_name(e)))
+        .collect::<Vec<_>>();
+    out.push_str("    def serialize(self):\n");
+    out.push_str(&format!(
+        "        return [{}]\n",
+        names
+            .iter()
+            .map(|n| format!("self.{}", n))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+
+    out.push('\n');
+    out.push_str(&render_bsatn(module, product));
+    out
+}
+
+/// Render the `bsatn_encode`/`bsatn_decode` pair for a product, driven by each
+/// element's `AlgebraicType`: options, arrays and references to generated types
+/// recurse. Inline (unnamed) products and non-option sums are not supported and
+/// make generation panic. Elements are encoded back-to-back in declaration order.
+fn render_bsatn(module: &ModuleDef, product: &ProductType) -> String {
+    let mut out = String::new();
+
+    out.push_str("    def bsatn_encode(self) -> bytes:\n");
+    out.push_str("        w = BsatnWriter()\n");
+    if product.elements.is_empty() {
+        out.push_str("        pass\n");
+    }
+    for element in product.elements.iter() {
+        let target = format!("self.{}", snake_case(field_name(element)));
+        out.push_str(&encode_lines(module, &element.algebraic_type, &target, 2));
+    }
+    out.push_str("        return w.finish()\n\n");
+
+    out.push_str("    @classmethod\n");
+    out.push_str("    def bsatn_decode(cls, buf):\n");
+    out.push_str("        r = buf if isinstance(buf, BsatnReader) else BsatnReader(buf)\n");
+    let args = product
+        .elements
+        .iter()
+        .map(|e| format!("{}={}", snake_case(field_name(e)), decode_expr(module, &e.algebraic_type)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    out.push_str(&format!("        return cls({})\n", args));
+    out
+}
+
+/// Emit encode statements for `ty` operating on the Python expression `expr`,
+/// each indented by `indent` levels of four spaces.
+fn encode_lines(module: &ModuleDef, ty: &AlgebraicType, expr: &str, indent: usize) -> String {
+    let pad = "    ".repeat(indent);
+    if let Some(method) = prim_method(ty) {
+        return format!("{}w.write_{}({})\n", pad, method, expr);
+    }
+    match ty {
+        AlgebraicType::Sum(sum) if as_option(sum).is_some() => {
+            let inner = as_option(sum).unwrap();
+            let mut out = String::new();
+            out.push_str(&format!("{}if {} is None:\n", pad, expr));
+            out.push_str(&format!("{}    w.write_u8(1)\n", pad));
+            out.push_str(&format!("{}else:\n", pad));
+            out.push_str(&format!("{}    w.write_u8(0)\n", pad));
+            out.push_str(&encode_lines(module, inner, expr, indent + 1));
+            out
+        }
+        AlgebraicType::Array(array) => {
+            let mut out = String::new();
+            out.push_str(&format!("{}w.write_u32(len({}))\n", pad, expr));
+            out.push_str(&format!("{}for _item in {}:\n", pad, expr));
+            out.push_str(&encode_lines(module, &array.elem_ty, "_item", indent + 1));
+            out
+        }
+        // A reference to a generated product/union owns its own encoding; its
+        // bytes are concatenated in place (no length prefix). A reference to an
+        // alias (option, primitive, array) has no class, so the aliased type is
+        // encoded in place.
+        AlgebraicType::Ref(r) => {
+            let target = resolve_type(module, *r);
+            let is_class = matches!(target, AlgebraicType::Product(_))
+                || (matches!(target, AlgebraicType::Sum(_)) && !is_option(target));
+            if is_class {
+                format!("{}w.write_bytes({}.bsatn_encode())\n", pad, expr)
+            } else {
+                encode_lines(module, target, expr, indent)
+            }
+        }
+        // Anything else (inline products/sums, U256/I256, and the other
+        // wide/exotic SATS types) has no faithful emitted encoding. Refuse
+        // rather than emit code that raises `AttributeError` at runtime.
+        _ => panic!(
+            "Python backend cannot BSATN-encode {ty:?}: only primitives, String, options, \
+             arrays, and references to generated types are supported"
+        ),
+    }
+}
+
+/// A Python expression that decodes a `ty` value from the reader `r`.
+fn decode_expr(module: &ModuleDef, ty: &AlgebraicType) -> String {
+    if let Some(method) = prim_method(ty) {
+        return format!("r.read_{}()", method);
+    }
+    match ty {
+        AlgebraicType::Sum(sum) if as_option(sum).is_some() => {
+            let inner = as_option(sum).unwrap();
+            format!("r.read_option(lambda: {})", decode_expr(module, inner))
+        }
+        AlgebraicType::Array(array) => {
+            format!("r.read_array(lambda: {})", decode_expr(module, &array.elem_ty))
+        }
+        // A reference to an alias (option, primitive, array) has no class, so
+        // the aliased type is decoded in place.
+        AlgebraicType::Ref(r) => match resolve_type(module, *r) {
+            target @ AlgebraicType::Sum(_) if is_option(target) => decode_expr(module, target),
+            AlgebraicType::Product(_) | AlgebraicType::Sum(_) => match ref_name(module, *r) {
+                Some(class) => format!("{}.bsatn_decode(r)", class),
+                None => panic!("Python backend cannot BSATN-decode unresolved type ref {r:?}"),
+            },
+            target => decode_expr(module, target),
+        },
+        // Anything else (inline products/sums, U256/I256, and the other
+        // wide/exotic SATS types) has no faithful emitted decoding. Refuse
+        // rather than silently return `None` and drop the field's bytes.
+        _ => panic!(
+            "Python backend cannot BSATN-decode {ty:?}: only primitives, String, options, \
+             arrays, and references to generated types are supported"
+        ),
+    }
+}
+
+/// BSATN method suffix for a fixed-width primitive, or `None` for composites.
+fn prim_method(ty: &AlgebraicType) -> Option<&'static str> {
+    Some(match ty {
+        AlgebraicType::Bool => "bool",
+        AlgebraicType::String => "string",
+        AlgebraicType::U8 => "u8",
+        AlgebraicType::U16 => "u16",
+        AlgebraicType::U32 => "u32",
+        AlgebraicType::U64 => "u64",
+        AlgebraicType::U128 => "u128",
+        AlgebraicType::I8 => "i8",
+        AlgebraicType::I16 => "i16",
+        AlgebraicType::I32 => "i32",
+        AlgebraicType::I64 => "i64",
+        AlgebraicType::I128 => "i128",
+        AlgebraicType::F32 => "f32",
+        AlgebraicType::F64 => "f64",
+        _ => return None,
+    })
+}
+
+/// Best-effort Python annotation for a SATS type.
+fn python_type(module: &ModuleDef, ty: &AlgebraicType) -> String {
+    match ty {
+        AlgebraicType::Bool => "bool".to_string(),
+        AlgebraicType::String => "str".to_string(),
+        AlgebraicType::F32 | AlgebraicType::F64 => "float".to_string(),
+        AlgebraicType::Ref(r) => ref_name(module, *r).unwrap_or_else(|| "typing.Any".to_string()),
+        AlgebraicType::Array(array) => {
+            format!("typing.List[{}]", python_type(module, &array.elem_ty))
+        }
+        // Two-variant some+unit sums are the SATS encoding of `Option<T>`.
+        AlgebraicType::Sum(sum) => match as_option(sum) {
+            Some(inner) => format!("typing.Optional[{}]", python_type(module, inner)),
+            None => "typing.Any".to_string(),
+        },
+        t if t.is_integer() => "int".to_string(),
+        _ => "typing.Any".to_string(),
+    }
+}
+
+/// Render a tagged-union type: one dataclass per variant carrying the `u8`-tag
+/// BSATN encoding, plus a dispatcher class whose `bsatn_decode` reads the tag
+/// and rebuilds the matching variant, so arbitrary SATS sums round-trip.
+fn render_union(module: &ModuleDef, class: &str, sum: &spacetimedb_lib::sats::SumType) -> String {
+    let mut out = String::from(PREAMBLE);
+    // Pull in any sibling classes a variant payload references.
+    for (stem, class_name) in referenced_types_in_sum(module, sum) {
+        out.push_str(&format!("from .{} import {}\n", stem, class_name));
+    }
+    out.push('\n');
+
+    let variant_classes = sum
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(i, variant)| {
+            let name = variant.name().map(pascal_case).unwrap_or_else(|| format!("Variant{}", i));
+            format!("{}_{}", class, name)
+        })
+        .collect::<Vec<_>>();
+
+    for (i, variant) in sum.variants.iter().enumerate() {
+        let vclass = &variant_classes[i];
+        let unit = variant.algebraic_type.is_unit();
+
+        out.push_str("@dataclass\n");
+        out.push_str(&format!("class {}:\n", vclass));
+        if !unit {
+            out.push_str(&format!("    value: {}\n", python_type(module, &variant.algebraic_type)));
+        }
+        // The discriminant mirrors the wire tag (the variant index). It carries
+        // a default so it trails the payload field, which dataclass ordering
+        // requires.
+        out.push_str(&format!("    tag: int = {}\n", i));
+        out.push('\n');
+
+        // Encode writes the variant index as a `u8` tag followed by the payload.
+        out.push_str("    def bsatn_encode(self) -> bytes:\n");
+        out.push_str("        w = BsatnWriter()\n");
+        out.push_str(&format!("        w.write_u8({})\n", i));
+        if !unit {
+            out.push_str(&encode_lines(module, &variant.algebraic_type, "self.value", 2));
+        }
+        out.push_str("        return w.finish()\n\n");
+
+        // The dispatcher has already consumed the tag, so this reads the payload
+        // only.
+        out.push_str("    @classmethod\n");
+        out.push_str("    def _bsatn_decode_payload(cls, r):\n");
+        if unit {
+            out.push_str("        return cls()\n");
+        } else {
+            out.push_str(&format!(
+                "        return cls(value={})\n",
+                decode_expr(module, &variant.algebraic_type)
+            ));
+        }
+        out.push('\n');
+    }
+
+    // Dispatcher: reads the `u8` tag and builds the matching variant.
+    out.push_str(&format!("class {}:\n", class));
+    out.push_str(&format!("    \"\"\"Tagged union over {}.\"\"\"\n\n", variant_classes.join(", ")));
+    out.push_str("    @staticmethod\n");
+    out.push_str("    def bsatn_decode(buf):\n");
+    out.push_str("        r = buf if isinstance(buf, BsatnReader) else BsatnReader(buf)\n");
+    if variant_classes.is_empty() {
+        out.push_str("        raise ValueError(\"empty sum type has no variants\")\n");
+        return out;
+    }
+    out.push_str("        tag = r.read_u8()\n");
+    for (i, vclass) in variant_classes.iter().enumerate() {
+        let kw = if i == 0 { "if" } else { "elif" };
+        out.push_str(&format!("        {} tag == {}:\n", kw, i));
+        out.push_str(&format!("            return {}._bsatn_decode_payload(r)\n", vclass));
+    }
+    out.push_str(&format!("        raise ValueError(f\"invalid {} tag {{tag}}\")\n", class));
+    out
+}
+
+/// Whether `ty` is the SATS encoding of `Option<T>` (a two-variant some+unit
+/// sum).
+fn is_option(ty: &AlgebraicType) -> bool {
+    matches!(ty, AlgebraicType::Sum(sum) if as_option(sum).is_some())
+}
+
+/// Return the inner type of a SATS `Option` sum (two variants ordered `some`
+/// carrying `T` then a `none`/unit variant), or `None` for a general sum.
+fn as_option(sum: &spacetimedb_lib::sats::SumType) -> Option<&AlgebraicType> {
+    if sum.variants.len() != 2 {
+        return None;
+    }
+    let some = &sum.variants[0];
+    let none = &sum.variants[1];
+    let ordered = some.name() == Some("some") && none.name() == Some("none");
+    if ordered && none.algebraic_type.is_unit() {
+        Some(&some.algebraic_type)
+    } else {
+        None
+    }
+}
+
+fn resolve_type(module: &ModuleDef, ty_ref: spacetimedb_lib::AlgebraicTypeRef) -> &AlgebraicType {
+    module
+        .typespace()
+        .get(ty_ref)
+        .expect("type ref must resolve within the module typespace")
+}
+
+fn resolve_product<'a>(module: &'a ModuleDef, ty_ref: spacetimedb_lib::AlgebraicTypeRef) -> &'a ProductType {
+    resolve_type(module, ty_ref)
+        .as_product()
+        .expect("table/type ref must resolve to a product type")
+}
+
+/// The `(module stem, class name)` of every standalone type referenced by a
+/// product's fields, de-duplicated, so the generated file can import them.
+fn referenced_types(module: &ModuleDef, product: &ProductType) -> Vec<(String, String)> {
+    let mut refs = Vec::new();
+    for element in product.elements.iter() {
+        collect_refs(&element.algebraic_type, &mut refs);
+    }
+    resolve_ref_imports(module, refs)
+}
+
+/// The `(module stem, class name)` of every standalone type referenced by a
+/// sum's variant payloads, de-duplicated, so the generated file can import them.
+fn referenced_types_in_sum(module: &ModuleDef, sum: &spacetimedb_lib::sats::SumType) -> Vec<(String, String)> {
+    let mut refs = Vec::new();
+    for variant in sum.variants.iter() {
+        collect_refs(&variant.algebraic_type, &mut refs);
+    }
+    resolve_ref_imports(module, refs)
+}
+
+/// Resolve a collected set of type refs to the `(module stem, class name)`
+/// imports a generated file needs, de-duplicated and filtered to defined types.
+fn resolve_ref_imports(
+    module: &ModuleDef,
+    mut refs: Vec<spacetimedb_lib::AlgebraicTypeRef>,
+) -> Vec<(String, String)> {
+    refs.sort();
+    refs.dedup();
+    refs.into_iter()
+        .filter_map(|r| {
+            module
+                .types()
+                .find(|t| t.ty == r)
+                .map(|t| (snake_case(type_name(t)), pascal_case(type_name(t))))
+        })
+        .collect()
+}
+
+/// Collect the type refs reachable through a field's `AlgebraicType`.
+fn collect_refs(ty: &AlgebraicType, out: &mut Vec<spacetimedb_lib::AlgebraicTypeRef>) {
+    match ty {
+        AlgebraicType::Ref(r) => out.push(*r),
+        AlgebraicType::Array(array) => collect_refs(&array.elem_ty, out),
+        AlgebraicType::Sum(sum) => {
+            for variant in sum.variants.iter() {
+                collect_refs(&variant.algebraic_type, out);
+            }
+        }
+        AlgebraicType::Product(product) => {
+            for element in product.elements.iter() {
+                collect_refs(&element.algebraic_type, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Name of the standalone type a `Ref` points at, if one is defined.
+fn ref_name(module: &ModuleDef, ty_ref: spacetimedb_lib::AlgebraicTypeRef) -> Option<String> {
+    module
+        .types()
+        .find(|t| t.ty == ty_ref)
+        .map(|t| pascal_case(type_name(t)))
+}
+
+fn type_name(typ: &TypeDef) -> &str {
+    typ.name.name.as_ref()
+}
+
+fn field_name(element: &ProductTypeElement) -> &str {
+    element.name().unwrap_or("")
+}
+
+/// The raw SATS name with any `r#` raw-identifier prefix stripped. This is the
+/// name the wire format uses, independent of the Python-side rename.
+fn sats_name(name: &str) -> &str {
+    name.strip_prefix("r#").unwrap_or(name)
+}
+
+/// Convert a SATS identifier to an idiomatic `snake_case` Python attribute,
+/// stripping any `r#` prefix and escaping Python keywords.
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for ch in sats_name(name).chars() {
+        if ch.is_uppercase() {
+            if prev_lower {
+                out.push('_');
+            }
+            out.extend(ch.to_lowercase());
+            prev_lower = false;
+        } else {
+            out.push(ch);
+            prev_lower = ch.is_alphanumeric();
+        }
+    }
+    escape_keyword(out)
+}
+
+/// Convert a SATS identifier to a `PascalCase` Python class name, stripping any
+/// `r#` prefix and escaping Python keywords.
+fn pascal_case(name: &str) -> String {
+    let pascal = sats_name(name)
+        .split(|c: char| c == '_' || c == ' ')
+        .filter(|s| !s.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    escape_keyword(pascal)
+}
+
+/// Append an underscore to names that collide with a Python keyword so the
+/// generated identifier stays valid (e.g. `class` -> `class_`).
+fn escape_keyword(name: String) -> String {
+    const KEYWORDS: &[&str] = &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield",
+    ];
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+    use spacetimedb_schema::def::{TableAccess, TableType};
+    use spacetimedb_schema::identifier::Identifier;
+
+    /// The quickstart `User` table: a `u64` primary key, an `option(String)`
+    /// field and a `bool`, mirroring the fixture in `test_codegen_direct.rs`.
+    fn user_module() -> ModuleDef {
+        let mut module = ModuleDef::new();
+        let user_type = AlgebraicType::Product(ProductType::new([
+            ProductTypeElement::new_named(AlgebraicType::U64, "identity"),
+            ProductTypeElement::new_named(AlgebraicType::option(AlgebraicType::String), "name"),
+            ProductTypeElement::new_named(AlgebraicType::Bool, "online"),
+        ]));
+        let user_type_ref = module.add_type(user_type);
+        module.add_table(TableDef {
+            name: Identifier::new("User").unwrap(),
+            product_type_ref: user_type_ref,
+            primary_key: Some(vec![0]),
+            indexes: vec![],
+            constraints: vec![],
+            table_type: TableType::User,
+            table_access: TableAccess::Public,
+        });
+        module
+    }
+
+    /// The contents of the generated file whose name matches `filename`.
+    fn file(files: &[(String, String)], filename: &str) -> String {
+        files
+            .iter()
+            .find(|(name, _)| name == filename)
+            .unwrap_or_else(|| panic!("missing generated file {filename}"))
+            .1
+            .clone()
+    }
+
+    #[test]
+    fn option_field_defaults_to_none() {
+        let files = generate(&user_module(), &Python);
+        let user = file(&files, "user.py");
+        assert!(
+            user.contains("name: typing.Optional[str] = None"),
+            "option field should be Optional with a None default:\n{user}"
+        );
+    }
+
+    #[test]
+    fn primary_key_emits_cache_lookup() {
+        let files = generate(&user_module(), &Python);
+        let user = file(&files, "user.py");
+        assert!(
+            user.contains("def find_by_identity(cls, conn, value) -> typing.Optional[User]"),
+            "primary key should emit a typed cache lookup:\n{user}"
+        );
+        assert!(user.contains("for row in conn.cache(cls):"));
+    }
+
+    #[test]
+    fn product_emits_bsatn_roundtrip() {
+        let files = generate(&user_module(), &Python);
+        let user = file(&files, "user.py");
+        assert!(user.contains("def bsatn_encode(self) -> bytes:"));
+        assert!(user.contains("def bsatn_decode(cls, buf):"));
+        // The option field writes a `u8` tag around its payload.
+        assert!(user.contains("w.write_u8(1)") && user.contains("w.write_u8(0)"));
+    }
+
+    #[test]
+    fn names_are_pythonized() {
+        assert_eq!(snake_case("r#type"), "type");
+        assert_eq!(snake_case("r#class"), "class_");
+        assert_eq!(snake_case("fooBar"), "foo_bar");
+        assert_eq!(pascal_case("user_profile"), "UserProfile");
+        assert_eq!(pascal_case("r#lambda"), "Lambda");
+    }
+}